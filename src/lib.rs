@@ -79,16 +79,20 @@
 //! println!("it's {:?}", uuid);  // produces: it's FourCC{u\xffi\x00}
 //! ```
 
+#![no_std]
 #![forbid(unsafe_code)]
 #![deny(rust_2018_idioms, future_incompatible, missing_docs)]
 
-use std::fmt;
+#[cfg(any(feature = "alloc", feature = "schemars"))]
+extern crate alloc;
+
+use core::fmt;
 
 /// A _four-character-code_ value.
 ///
 /// See the [module level documentation](index.html).
 #[derive(Clone,Copy,PartialEq,Eq,Hash)]
-#[cfg_attr(feature = "zerocopy", derive(zerocopy::FromBytes, zerocopy::AsBytes))]
+#[cfg_attr(feature = "zerocopy", derive(zerocopy::FromBytes, zerocopy::AsBytes, zerocopy::Unaligned))]
 #[repr(C, packed)]
 pub struct FourCC (
     pub [u8; 4]
@@ -98,11 +102,98 @@ impl<'a> From<&'a[u8; 4]> for FourCC {
         FourCC([buf[0], buf[1], buf[2], buf[3]])
     }
 }
+/// Panics if `buf` contains fewer than 4 bytes. Prefer [`FourCC::from_slice_checked`] when
+/// parsing untrusted data, such as a box header read from the network or disk.
+///
+/// Note: a `TryFrom<&[u8]>` impl is not offered alongside this `From` impl, since the standard
+/// library's blanket `impl<T, U: Into<T>> TryFrom<U> for T` would conflict with it - `From` and a
+/// hand-written `TryFrom` can never coexist for the same pair of types.
 impl<'a> From<&'a[u8]> for FourCC {
     fn from(buf: &[u8]) -> FourCC {
         FourCC([buf[0], buf[1], buf[2], buf[3]])
     }
 }
+/// Error returned by [`FourCC::from_slice_checked`] when a byte slice is too short to be
+/// converted into a `FourCC`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TryFromSliceError(());
+impl fmt::Display for TryFromSliceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("could not convert slice to FourCC: expected at least 4 bytes")
+    }
+}
+/// Error returned by `FourCC`'s [`FromStr`](core::str::FromStr) implementation.
+///
+/// Note this is stricter than serde's human-readable `Deserialize` impl: `FromStr` rejects
+/// non-printable bytes so that `"isom".parse::<FourCC>()`-style call sites get a useful parse
+/// error, but `Deserialize` accepts any 4-byte string so that a `FourCC` serialized to JSON
+/// round-trips whenever its bytes are valid UTF-8, including non-printable ones such as `\0`.
+/// A `FourCC` holding non-UTF8 bytes is rendered by [`Display`](fmt::Display) as escape
+/// sequences, and that escaped string is not accepted back by `Deserialize`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FourCcParseError {
+    /// The input was not exactly 4 bytes long (note: byte length, not `char` count).
+    WrongLength,
+    /// The input contained a byte outside the printable ASCII range.
+    InvalidByte,
+}
+impl fmt::Display for FourCcParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FourCcParseError::WrongLength => f.write_str("FourCC must be exactly 4 bytes long"),
+            FourCcParseError::InvalidByte => {
+                f.write_str("FourCC must consist of printable ASCII bytes")
+            }
+        }
+    }
+}
+impl core::str::FromStr for FourCC {
+    type Err = FourCcParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = s.as_bytes();
+        if bytes.len() != 4 {
+            return Err(FourCcParseError::WrongLength);
+        }
+        if !bytes.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+            return Err(FourCcParseError::InvalidByte);
+        }
+        Ok(FourCC([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+impl FourCC {
+    /// Attempts to construct a `FourCC` from the first 4 bytes of `buf`, returning an error
+    /// rather than panicking if `buf` contains fewer than 4 bytes.
+    pub fn from_slice_checked(buf: &[u8]) -> Result<FourCC, TryFromSliceError> {
+        if buf.len() < 4 {
+            Err(TryFromSliceError(()))
+        } else {
+            Ok(FourCC([buf[0], buf[1], buf[2], buf[3]]))
+        }
+    }
+}
+#[cfg(feature = "zerocopy")]
+impl FourCC {
+    /// Reinterprets a byte slice as a slice of `FourCC` values, without copying.
+    ///
+    /// Returns `None` if `buf.len()` is not a multiple of 4 (the size of a `FourCC`). Useful for
+    /// scanning a table of codes - such as the `compatible_brands` list in an MP4 `ftyp` box -
+    /// in place.
+    pub fn slice_from_bytes(buf: &[u8]) -> Option<&[FourCC]> {
+        zerocopy::Ref::<_, [FourCC]>::new_slice_unaligned(buf).map(|r| r.into_slice())
+    }
+
+    /// Reinterprets a mutable byte slice as a mutable slice of `FourCC` values, without copying.
+    ///
+    /// Returns `None` if `buf.len()` is not a multiple of 4 (the size of a `FourCC`).
+    pub fn slice_from_bytes_mut(buf: &mut [u8]) -> Option<&mut [FourCC]> {
+        zerocopy::Ref::<_, [FourCC]>::new_slice_unaligned(buf).map(|r| r.into_mut_slice())
+    }
+
+    /// Reinterprets a slice of `FourCC` values as the equivalent byte slice, without copying.
+    pub fn slice_as_bytes(codes: &[FourCC]) -> &[u8] {
+        zerocopy::AsBytes::as_bytes(codes)
+    }
+}
 impl From<u32> for FourCC {
     fn from(val: u32) -> FourCC {
         FourCC([
@@ -123,16 +214,37 @@ impl From<FourCC> for u32 {
 }
 impl fmt::Display for FourCC {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        match std::str::from_utf8(&self.0) {
+        match core::str::from_utf8(&self.0) {
             Ok(s) => f.write_str(s),
             Err(_) => {
                 // If we return fmt::Error, then for example format!() will panic, so we choose
                 // an alternative representation instead
-                let s = &self.0
-                    .iter()
-                    .flat_map(|b| std::ascii::escape_default(*b) )
-                    .collect::<Vec<u8>>()[..];
-                f.write_str(&String::from_utf8_lossy(s))
+                #[cfg(feature = "alloc")]
+                {
+                    use alloc::string::String;
+                    use alloc::vec::Vec;
+                    let s = &self.0
+                        .iter()
+                        .flat_map(|b| core::ascii::escape_default(*b) )
+                        .collect::<Vec<u8>>()[..];
+                    f.write_str(&String::from_utf8_lossy(s))
+                }
+                #[cfg(not(feature = "alloc"))]
+                {
+                    // Each byte escapes to at most 4 ASCII chars (e.g. `\xff`), and FourCC is
+                    // always 4 bytes, so a 16 byte stack buffer is always big enough, and no
+                    // allocation is required.
+                    let mut buf = [0u8; 16];
+                    let mut len = 0;
+                    for b in self.0.iter() {
+                        for e in core::ascii::escape_default(*b) {
+                            buf[len] = e;
+                            len += 1;
+                        }
+                    }
+                    // escape_default() only ever produces ASCII bytes, so this is always valid.
+                    f.write_str(core::str::from_utf8(&buf[..len]).unwrap_or(""))
+                }
             },
         }
     }
@@ -147,8 +259,8 @@ impl fmt::Debug for FourCC {
 
 #[cfg(feature = "schemars")]
 impl schemars::JsonSchema for FourCC {
-    fn schema_name() -> String {
-        "FourCC".to_string()
+    fn schema_name() -> alloc::string::String {
+        alloc::string::ToString::to_string("FourCC")
     }
     fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
         gen.subschema_for::<&str>()
@@ -158,61 +270,259 @@ impl schemars::JsonSchema for FourCC {
     }
 }
 
+/// For human-readable formats (e.g. JSON) a `FourCC` is serialized as its string form, the same
+/// as [`Display`](fmt::Display). For non-human-readable, binary formats (e.g. `serde_cbor`,
+/// `bincode`) the raw 4 bytes are serialized via `serialize_bytes`, since the string form is
+/// lossy for a `FourCC` containing non-UTF8 bytes (which is legal - see the
+/// [module level documentation](index.html)).
 #[cfg(feature = "serde")]
 impl serde::ser::Serialize for FourCC {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        serializer.collect_str(self)
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
     }
 }
 
 #[cfg(feature = "serde")]
-struct FromStrVisitor<T> {
-    expecting: &'static str,
-    ty: core::marker::PhantomData<T>,
-}
+struct FourCCVisitor;
 
 #[cfg(feature = "serde")]
-impl<T> FromStrVisitor<T> {
-    fn new(expecting: &'static str) -> Self {
-        FromStrVisitor {
-            expecting: expecting,
-            ty: core::marker::PhantomData,
-        }
+impl<'de> serde::de::Visitor<'de> for FourCCVisitor {
+    type Value = FourCC;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a FourCC, as a 4 byte string, 4 byte array, or big-endian u32")
     }
-}
 
-#[cfg(feature = "serde")]
-impl core::str::FromStr for FourCC {
-    type Err = u32;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(s.as_bytes().into())
+    fn visit_str<E>(self, s: &str) -> Result<FourCC, E>
+    where
+        E: serde::de::Error,
+    {
+        // Deliberately not `s.parse()`: `FromStr` rejects non-printable bytes, but any 4-byte
+        // string is a valid `FourCC` on the wire (see the module docs), so accept it directly to
+        // keep the round-trip for UTF-8 codes holding non-printable bytes such as `\0`.
+        self.visit_bytes(s.as_bytes())
     }
-}
 
-#[cfg(feature = "serde")]
-impl<'de, T> serde::de::Visitor<'de> for FromStrVisitor<T>
-where
-    T: core::str::FromStr,
-    T::Err: fmt::Display,
-{
-    type Value = T;
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<FourCC, E>
+    where
+        E: serde::de::Error,
+    {
+        if v.len() != 4 {
+            return Err(serde::de::Error::invalid_length(v.len(), &"4 bytes"));
+        }
+        FourCC::from_slice_checked(v).map_err(serde::de::Error::custom)
+    }
 
-    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-        formatter.write_str(self.expecting)
+    fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> Result<FourCC, E>
+    where
+        E: serde::de::Error,
+    {
+        self.visit_bytes(v)
     }
 
-    fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+    fn visit_u32<E>(self, v: u32) -> Result<FourCC, E>
     where
         E: serde::de::Error,
     {
-        s.parse().map_err(serde::de::Error::custom)
+        Ok(FourCC::from(v))
     }
 }
 
 #[cfg(feature = "serde")]
 impl<'de> serde::de::Deserialize<'de> for FourCC {
     fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        deserializer.deserialize_str(FromStrVisitor::new("FourCC"))
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(FourCCVisitor)
+        } else {
+            deserializer.deserialize_bytes(FourCCVisitor)
+        }
+    }
+}
+
+/// A 64-bit DRM-style format modifier, pairing a vendor id with a vendor-defined tiling or
+/// compression layout.
+///
+/// Mirrors the modifiers used by `drm-fourcc` and the Linux DRM/KMS and Vulkan APIs to qualify a
+/// pixel format code: the top 8 bits identify the vendor, and the low 56 bits encode a
+/// vendor-specific layout value.
+#[cfg(feature = "modifier")]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FourCCModifier(pub u64);
+
+#[cfg(feature = "modifier")]
+impl FourCCModifier {
+    /// Constructs a modifier from a vendor id and a vendor-defined value.
+    ///
+    /// Only the low 56 bits of `value` are retained; the high 8 bits come from `vendor`.
+    pub const fn new(vendor: u8, value: u64) -> FourCCModifier {
+        FourCCModifier(((vendor as u64) << 56) | (value & 0x00ff_ffff_ffff_ffff))
+    }
+
+    /// The vendor id occupying the top 8 bits of the modifier.
+    pub const fn vendor(&self) -> u8 {
+        (self.0 >> 56) as u8
+    }
+
+    /// The vendor-defined layout value occupying the low 56 bits of the modifier.
+    pub const fn value(&self) -> u64 {
+        self.0 & 0x00ff_ffff_ffff_ffff
+    }
+}
+
+#[cfg(feature = "modifier")]
+impl fmt::Display for FourCCModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}:{:#x}", self.vendor(), self.value())
+    }
+}
+
+#[cfg(feature = "modifier")]
+impl fmt::Debug for FourCCModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FourCCModifier({:#x}:{:#x})", self.vendor(), self.value())
+    }
+}
+
+#[cfg(feature = "modifier")]
+impl From<u64> for FourCCModifier {
+    fn from(val: u64) -> FourCCModifier {
+        FourCCModifier(val)
+    }
+}
+
+#[cfg(feature = "modifier")]
+impl From<FourCCModifier> for u64 {
+    fn from(val: FourCCModifier) -> u64 {
+        val.0
+    }
+}
+
+// Hand-written rather than `#[derive(Serialize, Deserialize)]`: this crate's "serde" feature
+// deliberately doesn't pull in `serde/derive` (see `FourCC`'s serde impls above), so the derive
+// macros aren't available.
+#[cfg(all(feature = "modifier", feature = "serde"))]
+impl serde::ser::Serialize for FourCCModifier {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+#[cfg(all(feature = "modifier", feature = "serde"))]
+impl<'de> serde::de::Deserialize<'de> for FourCCModifier {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <u64 as serde::de::Deserialize>::deserialize(deserializer).map(FourCCModifier)
+    }
+}
+
+/// A pixel format code paired with a vendor-specific [`FourCCModifier`].
+///
+/// Mirrors the `(format, modifier)` pairs used by DRM/KMS and Vulkan to describe tiled or
+/// compressed GPU buffer layouts, letting `FourCC` serve as the shared format vocabulary for
+/// both container parsers and GPU buffer descriptors.
+#[cfg(feature = "modifier")]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FormatWithModifier {
+    /// The pixel format code.
+    pub code: FourCC,
+    /// The vendor/layout modifier applied to `code`.
+    pub modifier: FourCCModifier,
+}
+
+#[cfg(feature = "modifier")]
+impl FormatWithModifier {
+    /// Constructs a `FormatWithModifier` from a format code and a modifier.
+    pub const fn new(code: FourCC, modifier: FourCCModifier) -> FormatWithModifier {
+        FormatWithModifier { code, modifier }
+    }
+}
+
+#[cfg(feature = "modifier")]
+impl fmt::Display for FormatWithModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.code, self.modifier)
+    }
+}
+
+#[cfg(feature = "modifier")]
+impl fmt::Debug for FormatWithModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FormatWithModifier({}:{})", self.code, self.modifier)
+    }
+}
+
+// Hand-written rather than `#[derive(Serialize, Deserialize)]`: this crate's "serde" feature
+// deliberately doesn't pull in `serde/derive` (see `FourCC`'s serde impls above), so the derive
+// macros aren't available.
+#[cfg(all(feature = "modifier", feature = "serde"))]
+impl serde::ser::Serialize for FormatWithModifier {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("FormatWithModifier", 2)?;
+        state.serialize_field("code", &self.code)?;
+        state.serialize_field("modifier", &self.modifier)?;
+        state.end()
+    }
+}
+
+#[cfg(all(feature = "modifier", feature = "serde"))]
+impl<'de> serde::de::Deserialize<'de> for FormatWithModifier {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FormatWithModifierVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for FormatWithModifierVisitor {
+            type Value = FormatWithModifier;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a struct with `code` and `modifier` fields")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<FormatWithModifier, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let code = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let modifier = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
+                Ok(FormatWithModifier { code, modifier })
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<FormatWithModifier, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let mut code = None;
+                let mut modifier = None;
+                while let Some(key) = map.next_key::<&str>()? {
+                    match key {
+                        "code" => code = Some(map.next_value()?),
+                        "modifier" => modifier = Some(map.next_value()?),
+                        other => {
+                            return Err(serde::de::Error::unknown_field(
+                                other,
+                                &["code", "modifier"],
+                            ))
+                        }
+                    }
+                }
+                let code = code.ok_or_else(|| serde::de::Error::missing_field("code"))?;
+                let modifier =
+                    modifier.ok_or_else(|| serde::de::Error::missing_field("modifier"))?;
+                Ok(FormatWithModifier { code, modifier })
+            }
+        }
+
+        deserializer.deserialize_struct(
+            "FormatWithModifier",
+            &["code", "modifier"],
+            FormatWithModifierVisitor,
+        )
     }
 }
 
@@ -231,4 +541,47 @@ mod tests {
         assert_eq!(0x41424344u32, FourCC(*b"ABCD").into());
         assert_eq!(FourCC(*b"ABCD"), 0x41424344u32.into());
     }
+
+    #[cfg(feature = "modifier")]
+    #[test]
+    fn modifier() {
+        let m = FourCCModifier::new(0x01, 0x02);
+        assert_eq!(0x01, m.vendor());
+        assert_eq!(0x02, m.value());
+        assert_eq!(m, FourCCModifier::from(u64::from(m)));
+
+        let f = FormatWithModifier::new(FourCC(*b"XR24"), m);
+        assert_eq!(FourCC(*b"XR24"), f.code);
+        assert_eq!(m, f.modifier);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_human_readable_round_trip() {
+        use serde_test::{assert_tokens, Configure, Token};
+
+        assert_tokens(&FourCC(*b"isom").readable(), &[Token::Str("isom")]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_binary_round_trip_non_utf8() {
+        // Non-UTF8 FourCC values are legal (see the module level documentation) and must
+        // round-trip exactly through non-human-readable formats, which serialize/deserialize
+        // the raw 4 bytes rather than the lossy escaped string form.
+        use serde_test::{assert_tokens, Configure, Token};
+
+        assert_tokens(&FourCC(*b"u\xffi\0").compact(), &[Token::Bytes(b"u\xffi\0")]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_binary_rejects_wrong_length() {
+        use serde_test::{assert_de_tokens_error, Compact, Token};
+
+        assert_de_tokens_error::<Compact<FourCC>>(
+            &[Token::Bytes(b"u\xffi\0\0")],
+            "invalid length 5, expected 4 bytes",
+        );
+    }
 }